@@ -18,9 +18,22 @@ pub use clap::Parser;
 use clap::{
     value_parser,
     ArgAction::{Set, SetTrue},
+    ValueEnum,
 };
 use std::path::PathBuf;
 
+/// Minimum allowed sampling interval, in seconds: the DHT22 needs spacing between reads, so
+/// shorter intervals reliably drive continuous `Timeout`/`Checksum` errors.
+pub const MIN_INTERVAL: u16 = 2;
+
+/// Output format for the periodic statistical summary.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SummaryFormat {
+    Text,
+    Csv,
+    Json,
+}
+
 /// Humidity & Temperature CLI datalogger for DHT22 sensor on Raspberry Pi.
 #[derive(Parser, Debug)]
 #[clap(
@@ -38,12 +51,16 @@ pub struct Args {
         short,
         long,
         default_value_t = 120,
-        value_parser = value_parser!(u16).range(2..)
+        value_parser = value_parser!(u16).range(MIN_INTERVAL as i64..)
     )]
     pub interval: u16,
     /// Print output as `<hum,temp>` to stdout (for use in unix pipeline).
     #[clap(short = 'P', long, action = SetTrue)]
     pub pipe: bool,
+    /// Print output as JSON Lines (one self-describing JSON object per reading) to stdout, for
+    /// log shippers and time-series ingesters.
+    #[clap(short, long, action = SetTrue)]
+    pub json: bool,
     /// Output CSV directory.
     #[clap(short, long, default_value = "~", value_parser)]
     pub directory: PathBuf,
@@ -55,6 +72,28 @@ pub struct Args {
     /// SIGUSR1).
     #[clap(long, action = SetTrue)]
     pub csv: bool,
+    /// Output SQLite database path: stores measures in a queryable `measures` table, as an
+    /// alternative (or addition) to CSV output.
+    #[clap(long, value_parser)]
+    pub sqlite: Option<PathBuf>,
+    /// Interval (in seconds) between statistical summary dumps; if unset, no summary is printed.
+    #[clap(long, value_parser)]
+    pub summary_interval: Option<u32>,
+    /// Format used to print the statistical summary.
+    #[clap(long, value_enum, default_value = "text")]
+    pub summary_format: SummaryFormat,
+    /// Route error messages (and measures) to the system log instead of stderr, for headless
+    /// operation under systemd/journald.
+    #[clap(long, action = SetTrue)]
+    pub syslog: bool,
+    /// Syslog facility to log under when `--syslog` is set (e.g. `daemon`, `user`,
+    /// `local0`..`local7`).
+    #[clap(long, default_value = "daemon")]
+    pub syslog_facility: String,
+    /// Unix domain socket path for the runtime control interface: accepts `get`, `csv on`/`csv
+    /// off` and `interval <secs>` commands.
+    #[clap(long, value_parser)]
+    pub control_socket: Option<PathBuf>,
     /// Mute standard output.
     #[clap(short, long, action = SetTrue)]
     pub quiet: bool,
@@ -15,6 +15,7 @@
 // this program. If not, see https://www.gnu.org/licenses/.
 
 use rppal::gpio::Error as RppalGpioError;
+use rusqlite::Error as RusqliteError;
 use std::{
     error::Error,
     fmt::{self, Display, Formatter},
@@ -40,6 +41,12 @@ pub enum ErrorKind {
     MsgPassingErr,
     /// Occurs when unable to create directory.
     MkDirErr(PathBuf, IoError),
+    /// Occurs when unable to open or write to the SQLite database.
+    DbError(PathBuf, RusqliteError),
+    /// Occurs when unable to bind the control socket.
+    SocketBindErr(PathBuf, IoError),
+    /// Occurs when unable to accept a connection on an already-bound control socket.
+    SocketAcceptErr(PathBuf, IoError),
     /// Any other error.
     Other(String),
 }
@@ -86,6 +93,21 @@ impl Display for ErrorKind {
                 path.display(),
                 err
             ),
+            Self::DbError(path, err) => {
+                write!(f, "unable to access database '{}': {}", path.display(), err)
+            }
+            Self::SocketBindErr(path, err) => write!(
+                f,
+                "unable to bind control socket '{}': {}",
+                path.display(),
+                err
+            ),
+            Self::SocketAcceptErr(path, err) => write!(
+                f,
+                "unable to accept connection on control socket '{}': {}",
+                path.display(),
+                err
+            ),
             Self::Other(msg) => write!(f, "{}", msg),
         }
     }
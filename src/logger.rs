@@ -0,0 +1,85 @@
+// datalogger: Humidity & Temperature CLI datalogger for DHT22 sensor on Raspberry Pi.
+// Copyright (C) 2022 Marco Radocchia
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see https://www.gnu.org/licenses/.
+
+//! Logging abstraction routing errors (and, optionally, measurements) either to the existing
+//! colorized stderr path or to the system log, for headless operation under systemd/journald.
+
+use crate::error::ErrorKind;
+use std::{io, process, str::FromStr};
+use syslog::{Facility, Formatter3164, Logger as SyslogLogger, LoggerBackend};
+
+/// Logging backend: either the existing colorized stderr path, or syslog.
+pub enum Logger {
+    Stderr,
+    Syslog(SyslogLogger<LoggerBackend, Formatter3164>),
+}
+
+impl Logger {
+    /// Build a `Logger`: a syslog connection on the given `facility` if `syslog` is `true`
+    /// (e.g. `"daemon"`, `"user"`, `"local0"`..`"local7"`), otherwise the existing stderr path.
+    pub fn new(syslog: bool, facility: &str) -> io::Result<Self> {
+        if !syslog {
+            return Ok(Self::Stderr);
+        }
+
+        let facility = Facility::from_str(facility).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid syslog facility '{facility}'"),
+            )
+        })?;
+
+        let formatter = Formatter3164 {
+            facility,
+            hostname: None,
+            process: "datalogger".into(),
+            pid: process::id() as i32,
+        };
+
+        let logger = syslog::unix(formatter)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+
+        Ok(Self::Syslog(logger))
+    }
+
+    /// Log an `ErrorKind`, mapping each variant to an appropriate syslog severity (falling back
+    /// to stderr if the syslog write itself fails).
+    pub fn log_error(&mut self, err: &ErrorKind) {
+        match self {
+            Self::Stderr => {
+                if err.colorize().is_err() {
+                    eprintln!("error: {err}.");
+                }
+            }
+            Self::Syslog(logger) => {
+                let result = match err {
+                    ErrorKind::GpioError(_) | ErrorKind::MaxRetries => logger.err(err.to_string()),
+                    _ => logger.warning(err.to_string()),
+                };
+                if result.is_err() {
+                    eprintln!("error: {err}.");
+                }
+            }
+        }
+    }
+
+    /// Log a formatted measurement line to syslog, if this logger routes measures there.
+    pub fn log_measure(&mut self, measure: &str) {
+        if let Self::Syslog(logger) = self {
+            let _ = logger.info(measure);
+        }
+    }
+}
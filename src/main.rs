@@ -16,20 +16,26 @@
 
 mod args;
 mod error;
+mod logger;
+mod stats;
 
-use args::{Args, Parser};
+use args::{Args, Parser, SummaryFormat, MIN_INTERVAL};
 use chrono::{DateTime, Local};
 use dht22_pi::{self, Reading, ReadingError};
 use error::ErrorKind;
+use logger::Logger;
+use rusqlite::Connection;
 use signal_hook::{consts::SIGUSR1, flag::register};
+use stats::Stats;
 use std::{
     fmt::{self, Display, Formatter},
     fs::{self, OpenOptions},
-    io::Write,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
     process,
     sync::{
         atomic::{AtomicBool, Ordering},
-        mpsc, Arc,
+        mpsc, Arc, Mutex,
     },
     thread,
     time::{Duration, Instant},
@@ -44,6 +50,7 @@ const MAX_RETRIES: u8 = 20;
 /// # Fields
 /// - reading: DHT22 sensor Reading
 /// - datetime: date & time of the measurement
+#[derive(Clone)]
 struct Measure {
     reading: Reading,
     datetime: DateTime<Local>,
@@ -69,6 +76,17 @@ impl Measure {
     fn to_pipe(&self) -> String {
         format!("{},{}", self.reading.humidity, self.reading.temperature)
     }
+
+    /// Format measurement as a single-line JSON object (JSON Lines), for the `--json` output mode
+    /// and the control socket `get` command.
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"timestamp\":\"{}\",\"humidity\":{},\"temperature\":{}}}",
+            self.datetime.to_rfc3339(),
+            self.reading.humidity,
+            self.reading.temperature
+        )
+    }
 }
 
 impl Display for Measure {
@@ -96,7 +114,7 @@ fn retry(retries: &mut u8) -> Result<()> {
     Ok(())
 }
 
-fn run(args: Args) -> Result<()> {
+fn run(args: Args, logger: Arc<Mutex<Logger>>) -> Result<()> {
     // Create directory (including parent directories if not present) if doesn't exist.
     if !args.directory.is_dir() {
         fs::create_dir_all(&args.directory)
@@ -106,6 +124,45 @@ fn run(args: Args) -> Result<()> {
     // Channel for message passing between main thread and output thread.
     let (tx, rx) = mpsc::channel::<Measure>();
 
+    // Shared runtime state: csv (toggled by SIGUSR1/the control socket, read by the output
+    // thread), interval (set by the control socket, read by the main loop) and latest (set by the
+    // main loop, read by the control socket).
+    let csv = Arc::new(AtomicBool::new(args.csv));
+    let interval = Arc::new(Mutex::new(args.interval));
+    let latest = Arc::new(Mutex::new(None::<Measure>));
+
+    // Control socket listener: answers `get`, `csv on`/`csv off` and `interval <secs>` commands
+    // from external clients connecting to `args.control_socket`. Bind happens here, synchronously,
+    // so a bad path/permissions/stale file surfaces as a `run()` error instead of silently killing
+    // a detached thread nobody joins.
+    if let Some(socket_path) = args.control_socket.clone() {
+        // Remove a stale socket file left over from a previous run, if any.
+        let _ = fs::remove_file(&socket_path);
+
+        let listener = UnixListener::bind(&socket_path)
+            .map_err(|err| ErrorKind::SocketBindErr(socket_path.to_owned(), err))?;
+
+        let csv = Arc::clone(&csv);
+        let interval = Arc::clone(&interval);
+        let latest = Arc::clone(&latest);
+
+        thread::spawn(move || -> Result<()> {
+            for stream in listener.incoming() {
+                let stream =
+                    stream.map_err(|err| ErrorKind::SocketAcceptErr(socket_path.to_owned(), err))?;
+
+                // Handle each connection on its own thread, so a stuck/slow client can't lock
+                // out the rest of the control interface.
+                let csv = Arc::clone(&csv);
+                let interval = Arc::clone(&interval);
+                let latest = Arc::clone(&latest);
+                thread::spawn(move || handle_control_client(stream, &csv, &interval, &latest));
+            }
+
+            Ok(())
+        });
+    }
+
     // Output thread.
     thread::spawn(move || -> Result<()> {
         // Register signal hook for SIGUSR1 events.
@@ -114,21 +171,42 @@ fn run(args: Args) -> Result<()> {
         register(SIGUSR1, Arc::clone(&sigusr1))
             .map_err(|_| "unable to register SIGUSR1 event handler")?;
 
-        // Local copy of args.csv which will be swapped every time SIGUSR1 signal is received,
-        // allowing user to swap CSV file printing behaviour (start/stop dumping measures to CSV
-        // file anytime at runtime).
-        let mut csv = args.csv;
+        // Open (or create) the SQLite database and its `measures` table, if `args.sqlite` is
+        // set, so readings can be queried later instead of (or alongside) CSV files.
+        let db = match &args.sqlite {
+            Some(path) => {
+                let conn = Connection::open(path)
+                    .map_err(|err| ErrorKind::DbError(path.to_owned(), err))?;
+                conn.execute(
+                    "CREATE TABLE IF NOT EXISTS measures (
+                        date        TEXT NOT NULL,
+                        time        TEXT NOT NULL,
+                        humidity    REAL NOT NULL,
+                        temperature REAL NOT NULL
+                    )",
+                    (),
+                )
+                .map_err(|err| ErrorKind::DbError(path.to_owned(), err))?;
+                Some((path.to_owned(), conn))
+            }
+            None => None,
+        };
+
+        // Running histogram + statistical summary state, dumped every `args.summary_interval`
+        // seconds (if set).
+        let mut stats = Stats::new();
+        let mut last_summary = Instant::now();
 
         for measure in rx {
             // If SIGUSR1 received (hence `sigusr1` is `true`), swap csv and restore `sigusr1` to
             // false.
             if sigusr1.load(Ordering::Relaxed) {
-                csv = !csv;
+                csv.fetch_xor(true, Ordering::Relaxed);
                 sigusr1.store(false, Ordering::Relaxed);
             }
 
             // If `csv` status is true, write data to CSV file.
-            if csv {
+            if csv.load(Ordering::Relaxed) {
                 let filename = Local::now().format(&args.format).to_string();
                 let csv_file = &args.directory.join(filename).with_extension("csv");
                 let mut file = OpenOptions::new()
@@ -153,14 +231,52 @@ fn run(args: Args) -> Result<()> {
                     .map_err(|err| ErrorKind::FileWriteErr(csv_file.to_owned(), err))?;
             }
 
+            // If `args.sqlite` is set, insert the measurement as a row in the `measures` table.
+            if let Some((path, conn)) = &db {
+                conn.execute(
+                    "INSERT INTO measures (date, time, humidity, temperature)
+                        VALUES (?1, ?2, ?3, ?4)",
+                    (
+                        measure.datetime.date().format("%Y-%m-%d").to_string(),
+                        measure.datetime.time().format("%H:%M:%S").to_string(),
+                        measure.reading.humidity as f64,
+                        measure.reading.temperature as f64,
+                    ),
+                )
+                .map_err(|err| ErrorKind::DbError(path.to_owned(), err))?;
+            }
+
+            // Update the running histograms and, once `args.summary_interval` seconds have
+            // elapsed, print the current statistical summary.
+            if let Some(summary_interval) = args.summary_interval {
+                stats.record(&measure.reading);
+
+                if last_summary.elapsed() >= Duration::from_secs(summary_interval.into()) {
+                    match args.summary_format {
+                        SummaryFormat::Csv => print!("{}", stats.to_csv()),
+                        SummaryFormat::Json => println!("{}", stats.to_json()),
+                        SummaryFormat::Text => println!("{stats}"),
+                    }
+                    last_summary = Instant::now();
+                }
+            }
+
             if !args.quiet {
-                // If `pipe` options is passed, print with "<hum>,<temp>" format to stdout, else
-                // print human readable values.
-                match args.pipe {
-                    true => println!("{}", measure.to_pipe()),
-                    false => println!("{}", measure),
+                // Select output format: JSON Lines, "<hum>,<temp>" pipe format, or human
+                // readable values, in that order of precedence.
+                if args.json {
+                    println!("{}", measure.to_json());
+                } else if args.pipe {
+                    println!("{}", measure.to_pipe());
+                } else {
+                    println!("{}", measure);
                 }
             }
+
+            // If `args.syslog` is set, also route the measurement to the system log.
+            if args.syslog {
+                logger.lock().unwrap().log_measure(&measure.to_string());
+            }
         }
 
         Ok(())
@@ -170,7 +286,7 @@ fn run(args: Args) -> Result<()> {
     loop {
         let instant = Instant::now();
         let mut retries = 0;
-        tx.send(Measure::new(
+        let measure = Measure::new(
             // Loop until valid result is obtained or max retries value is reached.
             loop {
                 match dht22_pi::read(args.pin) {
@@ -190,29 +306,93 @@ fn run(args: Args) -> Result<()> {
             },
             // datetime
             Local::now(),
-        ))
-        .map_err(|_| ErrorKind::MsgPassingErr)?;
-
-        // Sleep for `args.interval` corrected by the time spent measuring: if elapsed time is
-        // grates than the specified interval, this means the measuring process took longer than
-        // expected, so don't wait at all since we're already late.
-        if let Some(delay) =
-            Duration::from_secs(args.interval.into()).checked_sub(instant.elapsed())
-        {
+        );
+
+        // Share the latest reading with the control socket thread before handing it off to the
+        // output thread.
+        *latest.lock().unwrap() = Some(measure.clone());
+
+        tx.send(measure).map_err(|_| ErrorKind::MsgPassingErr)?;
+
+        // Sleep for the sampling interval corrected by the time spent measuring: if elapsed time
+        // is grates than the specified interval, this means the measuring process took longer
+        // than expected, so don't wait at all since we're already late. The interval can be
+        // adjusted live via the control socket.
+        let interval = *interval.lock().unwrap();
+        if let Some(delay) = Duration::from_secs(interval.into()).checked_sub(instant.elapsed()) {
             thread::sleep(delay);
         }
     }
 }
 
+/// Handle a single control socket connection: read line-based commands and write back their
+/// response, until the client disconnects.
+fn handle_control_client(
+    stream: UnixStream,
+    csv: &Arc<AtomicBool>,
+    interval: &Arc<Mutex<u16>>,
+    latest: &Arc<Mutex<Option<Measure>>>,
+) {
+    let mut writer = match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while matches!(reader.read_line(&mut line), Ok(n) if n > 0) {
+        let response = match line.trim() {
+            "get" => match &*latest.lock().unwrap() {
+                Some(measure) => measure.to_json(),
+                None => "null".to_string(),
+            },
+            "csv on" => {
+                csv.store(true, Ordering::Relaxed);
+                "ok".to_string()
+            }
+            "csv off" => {
+                csv.store(false, Ordering::Relaxed);
+                "ok".to_string()
+            }
+            cmd => match cmd.strip_prefix("interval ").map(str::parse::<u16>) {
+                // Enforce the same lower bound as the `--interval` CLI flag: the DHT22 needs
+                // spacing between reads, so a shorter interval would starve the main loop's
+                // sleep and hammer `dht22_pi::read` until `MAX_RETRIES` trips.
+                Some(Ok(secs)) if secs >= MIN_INTERVAL => {
+                    *interval.lock().unwrap() = secs;
+                    "ok".to_string()
+                }
+                Some(Ok(_)) | Some(Err(_)) => "error: invalid interval".to_string(),
+                None => "error: unknown command".to_string(),
+            },
+        };
+
+        if writeln!(writer, "{response}").is_err() {
+            return;
+        }
+        line.clear();
+    }
+}
+
 fn main() {
     // Parse CLI arguments.
     let args = Args::parse();
 
+    // Initialize the logging backend: syslog if `--syslog` was passed, else the existing
+    // colorized stderr path.
+    let logger = Arc::new(Mutex::new(
+        match Logger::new(args.syslog, &args.syslog_facility) {
+            Ok(logger) => logger,
+            Err(err) => {
+                eprintln!("error: unable to initialize syslog: {err}.");
+                process::exit(1);
+            }
+        },
+    ));
+
     // Run the program and catch errors.
-    if let Err(err) = run(args) {
-        if err.colorize().is_err() {
-            eprintln!("error: {err}.");
-        }
+    if let Err(err) = run(args, Arc::clone(&logger)) {
+        logger.lock().unwrap().log_error(&err);
         process::exit(1);
     }
 }
@@ -0,0 +1,248 @@
+// datalogger: Humidity & Temperature CLI datalogger for DHT22 sensor on Raspberry Pi.
+// Copyright (C) 2022 Marco Radocchia
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see https://www.gnu.org/licenses/.
+
+//! Running histograms and statistical summaries of humidity and temperature readings.
+
+use dht22_pi::Reading;
+use std::fmt::{self, Display, Formatter};
+
+/// Number of histogram boundaries used for both humidity and temperature (each histogram has
+/// `BUCKET_COUNT - 1` buckets).
+const BUCKET_COUNT: usize = 10;
+
+/// Humidity range (in percentage points) covered by the humidity histogram.
+const HUMIDITY_RANGE: (f64, f64) = (1.0, 100.0);
+
+/// Temperature range (in degrees Celsius) covered by the temperature histogram, spanning
+/// typical indoor/outdoor deployment readings.
+const TEMPERATURE_RANGE: (f64, f64) = (0.0, 50.0);
+
+/// Histogram bucketing scheme.
+#[derive(Debug, Clone, Copy)]
+enum Scheme {
+    /// `bucket_count` evenly spaced boundaries over `[range_min, range_max]`.
+    Linear,
+    /// `bucket_count` geometrically spaced boundaries over `[range_min, range_max]`; the first
+    /// bucket acts as an underflow bin.
+    Exponential,
+}
+
+/// Online histogram over a fixed range, bucketing samples and tracking running statistics.
+#[derive(Debug)]
+struct Histogram {
+    /// Precomputed, ascending bucket boundaries.
+    boundaries: Vec<f64>,
+    /// Per-bucket sample counts; `counts[i]` covers the half-open interval starting at
+    /// `boundaries[i]` and ending at `boundaries[i + 1]`.
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Histogram {
+    /// Build a histogram with `bucket_count` boundaries over `[range_min, range_max]`, using the
+    /// given bucketing `scheme`.
+    fn new(scheme: Scheme, range_min: f64, range_max: f64, bucket_count: usize) -> Self {
+        let boundaries: Vec<f64> = match scheme {
+            Scheme::Linear => (0..bucket_count)
+                .map(|i| {
+                    range_min + (range_max - range_min) * i as f64 / (bucket_count - 1) as f64
+                })
+                .collect(),
+            Scheme::Exponential => (0..bucket_count)
+                .map(|i| {
+                    (range_min * (range_max / range_min).powf(i as f64 / (bucket_count - 1) as f64))
+                        .round()
+                })
+                .collect(),
+        };
+
+        Self {
+            counts: vec![0; boundaries.len() - 1],
+            boundaries,
+            count: 0,
+            sum: 0.0,
+            min: f64::MAX,
+            max: f64::MIN,
+        }
+    }
+
+    /// Locate the bucket for `value` via binary search over the precomputed boundaries and
+    /// increment its count, clamping out-of-range values into the underflow/overflow bucket.
+    fn record(&mut self, value: f64) {
+        let idx = self
+            .boundaries
+            .partition_point(|&boundary| boundary <= value)
+            .saturating_sub(1)
+            .min(self.counts.len() - 1);
+        self.counts[idx] += 1;
+
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Mean of all recorded samples so far.
+    fn mean(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => self.sum / count as f64,
+        }
+    }
+
+    /// Minimum recorded sample (`0.0` if none have been recorded yet).
+    fn min(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            _ => self.min,
+        }
+    }
+
+    /// Maximum recorded sample (`0.0` if none have been recorded yet).
+    fn max(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            _ => self.max,
+        }
+    }
+
+    /// Format this histogram as a CSV row: `name,count,min,max,mean,bucket0,bucket1,...`.
+    fn to_csv_row(&self, name: &str) -> String {
+        let buckets = self
+            .counts
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{name},{count},{min},{max},{mean},{buckets}\n",
+            count = self.count,
+            min = self.min(),
+            max = self.max(),
+            mean = self.mean()
+        )
+    }
+
+    /// Format this histogram as a single-line JSON object.
+    fn to_json_object(&self, name: &str) -> String {
+        let buckets = self
+            .counts
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"name\":\"{name}\",\"count\":{count},\"min\":{min},\"max\":{max},\"mean\":{mean},\
+             \"buckets\":[{buckets}]}}",
+            count = self.count,
+            min = self.min(),
+            max = self.max(),
+            mean = self.mean()
+        )
+    }
+}
+
+impl Display for Histogram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return write!(f, "no samples recorded yet");
+        }
+
+        write!(
+            f,
+            "count: {}, min: {:.1}, max: {:.1}, mean: {:.1}, buckets: [",
+            self.count,
+            self.min,
+            self.max,
+            self.mean()
+        )?;
+        for (i, count) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{count}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Running statistical summary of humidity and temperature readings, updated on every
+/// [`Reading`] and periodically dumped by the output thread.
+#[derive(Debug)]
+pub struct Stats {
+    humidity: Histogram,
+    temperature: Histogram,
+}
+
+impl Stats {
+    /// Build a new, empty summary.
+    pub fn new() -> Self {
+        Self {
+            humidity: Histogram::new(
+                Scheme::Exponential,
+                HUMIDITY_RANGE.0,
+                HUMIDITY_RANGE.1,
+                BUCKET_COUNT,
+            ),
+            temperature: Histogram::new(
+                Scheme::Linear,
+                TEMPERATURE_RANGE.0,
+                TEMPERATURE_RANGE.1,
+                BUCKET_COUNT,
+            ),
+        }
+    }
+
+    /// Record `reading` in the humidity and temperature histograms.
+    pub fn record(&mut self, reading: &Reading) {
+        self.humidity.record(reading.humidity.into());
+        self.temperature.record(reading.temperature.into());
+    }
+
+    /// Format this summary as CSV: a header row followed by one row per histogram.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "name,count,min,max,mean,{}\n{}{}",
+            (0..self.humidity.counts.len())
+                .map(|i| format!("bucket{i}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.humidity.to_csv_row("humidity"),
+            self.temperature.to_csv_row("temperature"),
+        )
+    }
+
+    /// Format this summary as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"humidity\":{},\"temperature\":{}}}",
+            self.humidity.to_json_object("humidity"),
+            self.temperature.to_json_object("temperature"),
+        )
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Humidity summary:    {}", self.humidity)?;
+        write!(f, "Temperature summary: {}", self.temperature)
+    }
+}